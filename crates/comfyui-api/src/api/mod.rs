@@ -0,0 +1,5 @@
+pub mod progress;
+pub mod prompt;
+
+pub use progress::ProgressApi;
+pub use prompt::PromptApi;
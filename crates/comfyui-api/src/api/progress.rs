@@ -0,0 +1,288 @@
+use anyhow::Context;
+use bytes::Bytes;
+use futures_util::{future, Stream, StreamExt};
+use reqwest::Url;
+use serde::Deserialize;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::models::Image;
+
+/// The encoding of a live preview frame, taken from the format tag ComfyUI sends ahead of
+/// the raw image bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreviewFormat {
+    Jpeg,
+    Png,
+    /// A format tag this client doesn't recognize yet.
+    Unknown(u32),
+}
+
+impl From<u32> for PreviewFormat {
+    fn from(tag: u32) -> Self {
+        match tag {
+            1 => Self::Jpeg,
+            2 => Self::Png,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// A decoded message received from the ComfyUI `/ws` endpoint while a prompt executes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Update {
+    /// The number of prompts still queued, including the one currently running.
+    Status {
+        /// The number of prompts remaining in the queue.
+        queue_remaining: u64,
+    },
+    /// Progress of the sampler currently running inside the executing node.
+    Progress {
+        /// The current step.
+        value: u64,
+        /// The total number of steps.
+        max: u64,
+    },
+    /// The node currently executing, and the prompt it belongs to. `node: None` means
+    /// that prompt has finished.
+    Executing {
+        node: Option<String>,
+        prompt_id: Option<String>,
+    },
+    /// A live preview frame for the node currently executing.
+    Preview { format: PreviewFormat, bytes: Bytes },
+    /// A node finished executing and produced output images.
+    Executed {
+        /// The id of the node that produced the output.
+        node: String,
+        /// The prompt the output belongs to.
+        prompt_id: Option<String>,
+        /// The images saved by the node.
+        images: Vec<Image>,
+    },
+    /// The prompt failed to execute.
+    Error {
+        prompt_id: Option<String>,
+        message: String,
+    },
+}
+
+impl Update {
+    /// Whether this `Update` marks the end (success or failure) of `prompt_id`'s run.
+    pub fn is_terminal_for(&self, prompt_id: &str) -> bool {
+        match self {
+            Update::Executing { node: None, prompt_id: Some(id) } => id.as_str() == prompt_id,
+            Update::Error { prompt_id: Some(id), .. } => id.as_str() == prompt_id,
+            _ => false,
+        }
+    }
+}
+
+/// Raw `status` payload as sent by ComfyUI, nested under a `status` key.
+#[derive(Deserialize)]
+struct RawStatus {
+    exec_info: RawExecInfo,
+}
+
+#[derive(Deserialize)]
+struct RawExecInfo {
+    queue_remaining: u64,
+}
+
+/// Raw `executing` payload as sent by ComfyUI.
+#[derive(Deserialize)]
+struct RawExecuting {
+    node: Option<String>,
+    #[serde(default)]
+    prompt_id: Option<String>,
+}
+
+/// Raw `executed` payload as sent by ComfyUI.
+#[derive(Deserialize)]
+struct RawExecuted {
+    node: String,
+    #[serde(default)]
+    prompt_id: Option<String>,
+    output: RawOutput,
+}
+
+#[derive(Deserialize)]
+struct RawOutput {
+    #[serde(default)]
+    images: Vec<Image>,
+}
+
+/// Raw `execution_error` payload as sent by ComfyUI.
+#[derive(Deserialize)]
+struct RawExecutionError {
+    #[serde(default)]
+    prompt_id: Option<String>,
+    exception_message: String,
+}
+
+/// Raw `execution_interrupted` payload as sent by ComfyUI when a run is cancelled.
+#[derive(Deserialize)]
+struct RawExecutionInterrupted {
+    #[serde(default)]
+    prompt_id: Option<String>,
+}
+
+/// Raw, tagged representation of the JSON text frames sent over the ComfyUI websocket.
+///
+/// See <https://github.com/comfyanonymous/ComfyUI> for the (undocumented) message shapes.
+#[derive(Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+enum RawMessage {
+    Status {
+        status: RawStatus,
+    },
+    Progress {
+        value: u64,
+        max: u64,
+    },
+    Executing(RawExecuting),
+    Executed(RawExecuted),
+    ExecutionError(RawExecutionError),
+    ExecutionInterrupted(RawExecutionInterrupted),
+    /// Any message type we don't care about yet (e.g. `execution_start`, `execution_cached`).
+    #[serde(other)]
+    Unknown,
+}
+
+/// Struct representing a connection to the ComfyUI API `/ws` endpoint, used to observe
+/// live generation progress for prompts submitted with the same `client_id`.
+#[derive(Clone, Debug)]
+pub struct ProgressApi {
+    endpoint: Url,
+    client_id: uuid::Uuid,
+}
+
+impl ProgressApi {
+    /// Constructs a new `ProgressApi` from a ComfyUI base url and a `client_id`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - A `String` representation of the ComfyUI base url, e.g.
+    ///   `http://127.0.0.1:8188`.
+    /// * `client_id` - The `uuid::Uuid` shared with the `PromptApi` sending the prompts.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a new `ProgressApi` instance on success, or an error if url
+    /// parsing failed.
+    pub fn new(endpoint: String, client_id: uuid::Uuid) -> anyhow::Result<Self> {
+        Ok(Self::new_with_url(
+            Url::parse(&endpoint).context("failed to parse endpoint url")?,
+            client_id,
+        ))
+    }
+
+    /// Constructs a new `ProgressApi` from a ComfyUI base `endpoint` `Url`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The ComfyUI base `Url`.
+    /// * `client_id` - The `uuid::Uuid` shared with the `PromptApi` sending the prompts.
+    ///
+    /// # Returns
+    ///
+    /// A new `ProgressApi` instance.
+    pub fn new_with_url(endpoint: Url, client_id: uuid::Uuid) -> Self {
+        Self { endpoint, client_id }
+    }
+
+    /// Connects to the ComfyUI `/ws` endpoint and returns a `Stream` of decoded `Update`s.
+    ///
+    /// Frames this client doesn't recognize are silently dropped from the stream rather
+    /// than surfaced as an `Update`, so a consumer never sees a fabricated value for them.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the update `Stream` on success, or an error if the websocket
+    /// connection could not be established.
+    pub async fn connect(&self) -> anyhow::Result<impl Stream<Item = anyhow::Result<Update>>> {
+        let mut url = self.endpoint.clone();
+        url.set_path("/ws");
+        url.query_pairs_mut()
+            .append_pair("clientId", &self.client_id.to_string());
+        if url.scheme() == "http" {
+            let _ = url.set_scheme("ws");
+        } else if url.scheme() == "https" {
+            let _ = url.set_scheme("wss");
+        }
+
+        let (socket, _) = connect_async(url)
+            .await
+            .context("failed to connect to the ComfyUI websocket endpoint")?;
+
+        Ok(socket.filter_map(|message| {
+            let decoded = match message.context("failed to read websocket message") {
+                Ok(message) => decode(message),
+                Err(err) => Err(err),
+            };
+            future::ready(decoded.transpose())
+        }))
+    }
+}
+
+/// Decodes a single websocket `Message` into an `Update`, or `Ok(None)` if the frame
+/// should be skipped (e.g. a message type this client doesn't recognize yet).
+fn decode(message: Message) -> anyhow::Result<Option<Update>> {
+    match message {
+        Message::Text(text) => decode_text(&text),
+        Message::Binary(bytes) => decode_binary(bytes.into()),
+        _ => Err(anyhow::anyhow!("received unsupported websocket frame")),
+    }
+}
+
+fn decode_text(text: &str) -> anyhow::Result<Option<Update>> {
+    let raw: RawMessage =
+        serde_json::from_str(text).context("failed to parse websocket text frame")?;
+    Ok(match raw {
+        RawMessage::Status { status } => Some(Update::Status {
+            queue_remaining: status.exec_info.queue_remaining,
+        }),
+        RawMessage::Progress { value, max } => Some(Update::Progress { value, max }),
+        RawMessage::Executing(executing) => Some(Update::Executing {
+            node: executing.node,
+            prompt_id: executing.prompt_id,
+        }),
+        RawMessage::Executed(executed) => Some(Update::Executed {
+            node: executed.node,
+            prompt_id: executed.prompt_id,
+            images: executed.output.images,
+        }),
+        RawMessage::ExecutionError(error) => Some(Update::Error {
+            prompt_id: error.prompt_id,
+            message: error.exception_message,
+        }),
+        RawMessage::ExecutionInterrupted(interrupted) => Some(Update::Error {
+            prompt_id: interrupted.prompt_id,
+            message: "execution interrupted".to_string(),
+        }),
+        RawMessage::Unknown => None,
+    })
+}
+
+/// The event type tag ComfyUI sends ahead of a live preview's image bytes.
+const PREVIEW_EVENT_TYPE: u32 = 1;
+
+/// Decodes a binary frame.
+///
+/// ComfyUI prefixes binary frames with an 8 byte header: a big-endian `u32` event type
+/// (`1` for a live preview image) followed by a big-endian `u32` image format tag, with the
+/// raw image bytes making up the rest of the frame. Frames whose event type isn't a known
+/// preview are skipped rather than misdecoded as one.
+fn decode_binary(bytes: Bytes) -> anyhow::Result<Option<Update>> {
+    if bytes.len() < 8 {
+        return Err(anyhow::anyhow!("binary websocket frame too short"));
+    }
+    let event_type = u32::from_be_bytes(bytes[0..4].try_into().expect("checked length above"));
+    if event_type != PREVIEW_EVENT_TYPE {
+        return Ok(None);
+    }
+    let format = u32::from_be_bytes(bytes[4..8].try_into().expect("checked length above"));
+    Ok(Some(Update::Preview {
+        format: PreviewFormat::from(format),
+        bytes: bytes.slice(8..),
+    }))
+}
@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::models::{Image, Prompt};
+
+/// A content-addressed cache of generation results, keyed by the SHA-256 digest of a
+/// `Prompt`'s canonical JSON representation.
+///
+/// Because ComfyUI is deterministic for a fixed seed, an identical `Prompt` always
+/// produces the same images, so a cache hit can skip the ComfyUI round trip entirely.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// A cached result as stored on disk: one JSON file per cache key, recording the
+/// `prompt_id` the images were originally generated under and the images themselves,
+/// including the raw bytes of each.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    prompt_id: String,
+    images: Vec<Image>,
+}
+
+impl Cache {
+    /// Opens (creating if necessary) a `Cache` rooted at `dir`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory results are cached under, typically the bot's `db_path`.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the `Cache` on success, or an error if `dir` could not be
+    /// created.
+    pub fn new(dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).context("failed to create cache directory")?;
+        Ok(Self { dir })
+    }
+
+    /// Computes the cache key for `prompt`: the lowercase hex SHA-256 digest of the bytes
+    /// of its canonical JSON serialization.
+    ///
+    /// `Prompt`'s node map serializes in whatever order its own map type iterates, which
+    /// is not stable across process restarts. Going through `serde_json::to_value` first
+    /// (the same path `dot.rs` uses) re-keys every object into `serde_json`'s own `Map`,
+    /// which iterates in sorted order, so the resulting bytes are canonical and the digest
+    /// is stable across restarts.
+    pub fn key(prompt: &Prompt) -> anyhow::Result<String> {
+        let value = serde_json::to_value(prompt).context("failed to serialize prompt")?;
+        let bytes = serde_json::to_vec(&value).context("failed to serialize prompt")?;
+        Ok(hex::encode(Sha256::digest(bytes)))
+    }
+
+    /// Looks up a previously cached result for `prompt`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(Some(images))` on a cache hit, `Ok(None)` on a miss, or an error if the cache
+    /// entry exists but could not be read.
+    pub fn get(&self, prompt: &Prompt) -> anyhow::Result<Option<Vec<Image>>> {
+        let path = self.entry_path(&Self::key(prompt)?);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(&path).context("failed to read cache entry")?;
+        let entry: CacheEntry =
+            serde_json::from_slice(&bytes).context("failed to parse cache entry")?;
+        Ok(Some(entry.images))
+    }
+
+    /// Persists the result of running `prompt` under its cache key.
+    pub fn put(&self, prompt: &Prompt, prompt_id: String, images: Vec<Image>) -> anyhow::Result<()> {
+        let path = self.entry_path(&Self::key(prompt)?);
+        let entry = CacheEntry { prompt_id, images };
+        let bytes = serde_json::to_vec(&entry).context("failed to serialize cache entry")?;
+        std::fs::write(path, bytes).context("failed to write cache entry")
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `fetch_history_images` deserializes `Image` from `/history` JSON, which never
+    /// contains `bytes`, so that field must default rather than fail to parse. But a cache
+    /// entry is *our own* serialization of an already-populated `Image`, and must round-trip
+    /// those bytes rather than silently drop them, or a cache hit would hand the bot an
+    /// image with nothing to send.
+    #[test]
+    fn cache_entry_round_trip_preserves_image_bytes() {
+        let image = Image {
+            filename: "output.png".to_string(),
+            subfolder: String::new(),
+            type_: "output".to_string(),
+            bytes: bytes::Bytes::from_static(b"not-actually-a-png"),
+        };
+        let entry = CacheEntry {
+            prompt_id: "prompt-id".to_string(),
+            images: vec![image.clone()],
+        };
+
+        let serialized = serde_json::to_vec(&entry).expect("failed to serialize cache entry");
+        let deserialized: CacheEntry =
+            serde_json::from_slice(&serialized).expect("failed to parse cache entry");
+
+        assert_eq!(deserialized.images.len(), 1);
+        assert_eq!(deserialized.images[0].bytes, image.bytes);
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt::Write as _;
+
+use serde_json::Value;
+
+use crate::models::Prompt;
+
+/// Extension trait for exporting a `Prompt` workflow graph as a Graphviz DOT digraph.
+///
+/// This is mostly useful for debugging why a `Setter` failed to find a node: pipe the
+/// output through `dot -Tpng` to see the graph the heuristic setters are searching.
+pub trait DotExt {
+    /// Renders the workflow as a Graphviz `digraph`, with one vertex per node id labeled
+    /// by its `class_type`, and one edge per input that links to another node's output.
+    fn to_dot(&self) -> String;
+}
+
+impl DotExt for Prompt {
+    fn to_dot(&self) -> String {
+        let nodes = match serde_json::to_value(self) {
+            Ok(Value::Object(nodes)) => nodes,
+            _ => return "digraph prompt {}\n".to_string(),
+        };
+
+        let mut dot = String::from("digraph prompt {\n");
+
+        for (id, node) in &nodes {
+            let class_type = node
+                .get("class_type")
+                .and_then(Value::as_str)
+                .unwrap_or("?");
+            let _ = writeln!(dot, "  \"{id}\" [label=\"{id}: {class_type}\"];");
+        }
+
+        for (id, node) in &nodes {
+            let Some(inputs) = node.get("inputs").and_then(Value::as_object) else {
+                continue;
+            };
+            for (field, value) in inputs {
+                let Some(link) = value.as_array() else {
+                    continue;
+                };
+                let [Value::String(src), Value::Number(output_index)] = link.as_slice() else {
+                    continue;
+                };
+                let _ =
+                    writeln!(dot, "  \"{src}\" -> \"{id}\" [label=\"{field} ({output_index})\"];");
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -0,0 +1,307 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use futures_util::StreamExt;
+use reqwest::Url;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::{
+    api::{progress::Update, PromptApi, ProgressApi},
+    models::{Image, Prompt},
+};
+
+pub mod cache;
+pub mod dot;
+pub mod getter;
+pub mod pool;
+pub mod setter;
+
+pub use cache::Cache;
+pub use dot::DotExt;
+pub use pool::ComfyPool;
+pub use setter::{InsertExt, LoraSetter, PromptSetter, SeedSetter, SetterExt};
+
+const DEFAULT_ENDPOINT: &str = "http://127.0.0.1:8188";
+
+/// How long `run_prompt` waits for a terminal update before giving up on the websocket and
+/// falling back to whatever `/history` reports.
+const GENERATION_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// A high level client that executes ComfyUI workflows end-to-end: submitting a `Prompt`
+/// and collecting the resulting `Image`s.
+#[derive(Clone, Debug)]
+pub struct Comfy {
+    client: reqwest::Client,
+    endpoint: Url,
+    prompt_api: PromptApi,
+    progress_api: ProgressApi,
+    cache: Option<Cache>,
+}
+
+impl Comfy {
+    /// Constructs a new `Comfy` client pointed at the default local ComfyUI endpoint.
+    pub fn new() -> anyhow::Result<Self> {
+        Self::new_with_url(DEFAULT_ENDPOINT.to_string())
+    }
+
+    /// Constructs a new `Comfy` client pointed at the given ComfyUI base `endpoint`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The base url of the ComfyUI instance to connect to.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a new `Comfy` instance on success, or an error if url parsing
+    /// failed.
+    pub fn new_with_url(endpoint: String) -> anyhow::Result<Self> {
+        let client_id = uuid::Uuid::new_v4();
+        let client = reqwest::Client::new();
+        let prompt_endpoint = format!("{}/prompt", endpoint.trim_end_matches('/'));
+        // Ensure a trailing slash so `Url::join` appends to, rather than replaces, the
+        // last path segment when building the `/history` and `/view` urls below.
+        let base = format!("{}/", endpoint.trim_end_matches('/'));
+        Ok(Self {
+            endpoint: Url::parse(&base).context("failed to parse endpoint url")?,
+            prompt_api: PromptApi::new(client.clone(), prompt_endpoint, client_id)?,
+            progress_api: ProgressApi::new(endpoint, client_id)?,
+            client,
+            cache: None,
+        })
+    }
+
+    /// Like [`Comfy::new_with_url`], but additionally caches generation results under
+    /// `cache_dir`, keyed by a content hash of the `Prompt`. Repeating an identical
+    /// workflow returns the previously generated images without contacting ComfyUI.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The base url of the ComfyUI instance to connect to.
+    /// * `cache_dir` - The directory cached results are stored under.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a new `Comfy` instance on success, or an error if url parsing
+    /// or creating `cache_dir` failed.
+    pub fn new_with_cache(endpoint: String, cache_dir: impl Into<std::path::PathBuf>) -> anyhow::Result<Self> {
+        Ok(Self {
+            cache: Some(Cache::new(cache_dir)?),
+            ..Self::new_with_url(endpoint)?
+        })
+    }
+
+    /// Fetches this backend's current queue depth (prompts running plus pending) from
+    /// ComfyUI's `/queue` endpoint.
+    ///
+    /// `ComfyPool` uses this over HTTP rather than a second websocket connection, because
+    /// ComfyUI keys its socket map by `client_id` and a second connection under the same
+    /// `client_id` `run_prompt` uses would steal that prompt's `executing`/`executed`
+    /// frames instead of just observing `status`.
+    pub async fn queue_depth(&self) -> anyhow::Result<u64> {
+        let queue_url = self
+            .endpoint
+            .join("queue")
+            .context("failed to build queue url")?;
+        let queue: Value = self
+            .client
+            .get(queue_url)
+            .send()
+            .await
+            .context("failed to fetch queue status")?
+            .error_for_status()
+            .context("queue request returned an error status")?
+            .json()
+            .await
+            .context("failed to parse queue status")?;
+
+        let count = |key: &str| {
+            queue
+                .get(key)
+                .and_then(Value::as_array)
+                .map_or(0, Vec::len)
+        };
+        Ok((count("queue_running") + count("queue_pending")) as u64)
+    }
+
+    /// Submits `prompt` to ComfyUI and waits for the resulting images.
+    ///
+    /// # Arguments
+    ///
+    /// * `prompt` - The `Prompt` to execute.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the generated `Image`s on success, or an error if the prompt
+    /// could not be executed.
+    pub async fn execute_prompt(&self, prompt: &Prompt) -> anyhow::Result<Vec<Image>> {
+        self.execute_prompt_with_updates(prompt, None).await
+    }
+
+    /// Like [`Comfy::execute_prompt`], but bypasses the result cache even if one is
+    /// configured. Use this for workflows containing a randomized seed node, where a
+    /// previous cache entry would no longer reflect what the workflow currently produces.
+    pub async fn execute_prompt_no_cache(&self, prompt: &Prompt) -> anyhow::Result<Vec<Image>> {
+        self.run_prompt(prompt, None).await.map(|(_, images)| images)
+    }
+
+    /// Like [`Comfy::execute_prompt`], but additionally forwards live [`Update`]s over
+    /// `updates` while the prompt runs, so a caller (e.g. the Telegram bot) can render a
+    /// progress bar or live preview thumbnails.
+    ///
+    /// # Arguments
+    ///
+    /// * `prompt` - The `Prompt` to execute.
+    /// * `updates` - An optional `mpsc::Sender` that receives `Update`s as they arrive.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the generated `Image`s on success, or an error if the prompt
+    /// could not be executed.
+    pub async fn execute_prompt_with_updates(
+        &self,
+        prompt: &Prompt,
+        updates: Option<mpsc::Sender<Update>>,
+    ) -> anyhow::Result<Vec<Image>> {
+        if let Some(cache) = &self.cache {
+            if let Some(images) = cache.get(prompt).context("failed to read result cache")? {
+                return Ok(images);
+            }
+        }
+
+        let (prompt_id, images) = self.run_prompt(prompt, updates).await?;
+
+        if let Some(cache) = &self.cache {
+            cache
+                .put(prompt, prompt_id, images.clone())
+                .context("failed to write result cache")?;
+        }
+
+        Ok(images)
+    }
+
+    /// Submits `prompt` to ComfyUI and collects the resulting images, without consulting
+    /// or populating the result cache.
+    ///
+    /// The websocket is connected *before* the prompt is submitted, so no frame is missed,
+    /// and is read until ComfyUI reports this prompt finished (or failed) rather than being
+    /// torn down as soon as the submission POST returns — that POST only confirms the
+    /// prompt was queued, long before generation itself has happened.
+    ///
+    /// If no terminal update for this prompt arrives within `GENERATION_TIMEOUT` (the
+    /// websocket stalled, or the run was interrupted without a matching frame), this falls
+    /// back to whatever `/history` reports rather than waiting forever.
+    async fn run_prompt(
+        &self,
+        prompt: &Prompt,
+        updates: Option<mpsc::Sender<Update>>,
+    ) -> anyhow::Result<(String, Vec<Image>)> {
+        let mut stream = self.progress_api.connect().await?;
+
+        let response = self
+            .prompt_api
+            .send(prompt)
+            .await
+            .context("failed to submit prompt")?;
+        let prompt_id = response.prompt_id;
+
+        let wait_for_terminal = async {
+            while let Some(update) = stream.next().await {
+                let Ok(update) = update else { continue };
+
+                let terminal = update.is_terminal_for(&prompt_id);
+                let failed =
+                    matches!(&update, Update::Error { prompt_id: Some(id), .. } if id.as_str() == prompt_id);
+
+                if let Some(updates) = &updates {
+                    let _ = updates.send(update.clone()).await;
+                }
+
+                if failed {
+                    if let Update::Error { message, .. } = update {
+                        return Err(anyhow::anyhow!("prompt execution failed: {message}"));
+                    }
+                }
+                if terminal {
+                    break;
+                }
+            }
+            Ok(())
+        };
+
+        // A timeout (stalled socket) and a stream that simply closes without a terminal
+        // frame are both handled the same way: fall through to `/history`, which reports
+        // whatever ComfyUI actually finished regardless of what this socket saw.
+        match tokio::time::timeout(GENERATION_TIMEOUT, wait_for_terminal).await {
+            Ok(Ok(())) | Err(_) => {}
+            Ok(Err(err)) => return Err(err),
+        }
+
+        let images = self.fetch_history_images(&prompt_id).await?;
+        Ok((prompt_id, images))
+    }
+
+    /// Fetches the output images ComfyUI recorded for `prompt_id` in `/history`, and
+    /// downloads each one's bytes via `/view`.
+    async fn fetch_history_images(&self, prompt_id: &str) -> anyhow::Result<Vec<Image>> {
+        let history_url = self
+            .endpoint
+            .join(&format!("history/{prompt_id}"))
+            .context("failed to build history url")?;
+        let history: Value = self
+            .client
+            .get(history_url)
+            .send()
+            .await
+            .context("failed to fetch prompt history")?
+            .error_for_status()
+            .context("history request returned an error status")?
+            .json()
+            .await
+            .context("failed to parse prompt history")?;
+
+        let Some(outputs) = history
+            .get(prompt_id)
+            .and_then(|entry| entry.get("outputs"))
+            .and_then(Value::as_object)
+        else {
+            return Ok(Vec::new());
+        };
+
+        let mut images = Vec::new();
+        for node_output in outputs.values() {
+            let Some(entries) = node_output.get("images").and_then(Value::as_array) else {
+                continue;
+            };
+            for entry in entries {
+                let Ok(mut image) = serde_json::from_value::<Image>(entry.clone()) else {
+                    continue;
+                };
+                image.bytes = self.fetch_image_bytes(&image).await?;
+                images.push(image);
+            }
+        }
+        Ok(images)
+    }
+
+    /// Downloads the raw bytes of a saved `Image` via ComfyUI's `/view` endpoint.
+    async fn fetch_image_bytes(&self, image: &Image) -> anyhow::Result<bytes::Bytes> {
+        let mut view_url = self.endpoint.join("view").context("failed to build view url")?;
+        view_url
+            .query_pairs_mut()
+            .append_pair("filename", &image.filename)
+            .append_pair("subfolder", &image.subfolder)
+            .append_pair("type", &image.type_);
+
+        self.client
+            .get(view_url)
+            .send()
+            .await
+            .context("failed to fetch image bytes")?
+            .error_for_status()
+            .context("image fetch returned an error status")?
+            .bytes()
+            .await
+            .context("failed to read image bytes")
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::Context;
+
+use crate::{
+    comfy::Comfy,
+    models::{Image, Prompt},
+};
+
+/// The interval at which a `ComfyPool` polls its backends for health and queue depth, by
+/// default.
+const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A single backend in a `ComfyPool`: a `Comfy` client plus its tracked queue depth and
+/// health.
+struct Backend {
+    url: String,
+    comfy: Comfy,
+    /// The queue depth ComfyUI itself last reported for this backend, via `/queue`. Not
+    /// incremented locally on submission, since ComfyUI's own report already reflects the
+    /// prompt the instant it's queued.
+    queue_depth: AtomicU64,
+    healthy: AtomicBool,
+}
+
+/// Load-balances prompt execution across a pool of ComfyUI backends.
+///
+/// Each incoming prompt is dispatched to the least-loaded backend that last responded to
+/// a health check; unreachable backends are evicted from consideration and re-added once
+/// they respond again.
+#[derive(Clone)]
+pub struct ComfyPool {
+    backends: Arc<Vec<Backend>>,
+}
+
+impl ComfyPool {
+    /// Constructs a `ComfyPool` from a list of ComfyUI backend base urls, polling each
+    /// backend's health every `DEFAULT_HEALTH_CHECK_INTERVAL`.
+    pub fn new(urls: Vec<String>) -> anyhow::Result<Self> {
+        Self::new_with_options(urls, None, DEFAULT_HEALTH_CHECK_INTERVAL)
+    }
+
+    /// Like [`ComfyPool::new`], but every backend caches generation results under
+    /// `cache_dir`, the same way [`Comfy::new_with_cache`] does for a single backend.
+    /// Because the cache key is a content hash of the `Prompt`, a hit is shared across
+    /// backends regardless of which one originally generated it.
+    pub fn new_with_cache(urls: Vec<String>, cache_dir: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        Self::new_with_options(urls, Some(cache_dir.into()), DEFAULT_HEALTH_CHECK_INTERVAL)
+    }
+
+    /// Like [`ComfyPool::new`], but polls backend health every `health_check_interval`.
+    pub fn new_with_health_check_interval(
+        urls: Vec<String>,
+        health_check_interval: Duration,
+    ) -> anyhow::Result<Self> {
+        Self::new_with_options(urls, None, health_check_interval)
+    }
+
+    fn new_with_options(
+        urls: Vec<String>,
+        cache_dir: Option<PathBuf>,
+        health_check_interval: Duration,
+    ) -> anyhow::Result<Self> {
+        let backends = urls
+            .into_iter()
+            .map(|url| {
+                let comfy = match &cache_dir {
+                    Some(cache_dir) => Comfy::new_with_cache(url.clone(), cache_dir.clone())?,
+                    None => Comfy::new_with_url(url.clone())?,
+                };
+                Ok(Backend {
+                    comfy,
+                    url,
+                    queue_depth: AtomicU64::new(0),
+                    healthy: AtomicBool::new(true),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let backends = Arc::new(backends);
+
+        tokio::spawn(health_check_loop(backends.clone(), health_check_interval));
+
+        Ok(Self { backends })
+    }
+
+    /// Submits `prompt` to the least-loaded healthy backend in the pool.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the generated `Image`s on success, or an error if no healthy
+    /// backend is available or the prompt could not be executed.
+    pub async fn execute_prompt(&self, prompt: &Prompt) -> anyhow::Result<Vec<Image>> {
+        let backend = self
+            .pick_backend()
+            .context("no healthy ComfyUI backend available")?;
+
+        backend.comfy.execute_prompt(prompt).await
+    }
+
+    /// Like [`ComfyPool::execute_prompt`], but bypasses the result cache even if one is
+    /// configured. Use this for prompts containing a randomized seed, where a previous
+    /// cache entry would never be hit again anyway and would only grow the cache on disk.
+    pub async fn execute_prompt_no_cache(&self, prompt: &Prompt) -> anyhow::Result<Vec<Image>> {
+        let backend = self
+            .pick_backend()
+            .context("no healthy ComfyUI backend available")?;
+
+        backend.comfy.execute_prompt_no_cache(prompt).await
+    }
+
+    /// Picks the healthy backend with the lowest outstanding queue depth.
+    fn pick_backend(&self) -> Option<&Backend> {
+        self.backends
+            .iter()
+            .filter(|backend| backend.healthy.load(Ordering::SeqCst))
+            .min_by_key(|backend| backend.queue_depth.load(Ordering::SeqCst))
+    }
+}
+
+/// Periodically polls every backend's `/system_stats` endpoint to track health, and its
+/// `/queue` endpoint to track queue depth for load-balancing.
+///
+/// Both are plain HTTP requests, deliberately not a second websocket connection: ComfyUI
+/// keys its socket map by `client_id`, and a second connection under the same `client_id`
+/// a `run_prompt` call has open would steal that prompt's `executing`/`executed` frames
+/// instead of just observing `status`.
+async fn health_check_loop(backends: Arc<Vec<Backend>>, interval: Duration) {
+    let client = reqwest::Client::new();
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        for backend in backends.iter() {
+            let healthy = client
+                .get(format!(
+                    "{}/system_stats",
+                    backend.url.trim_end_matches('/')
+                ))
+                .send()
+                .await
+                .map(|response| response.status().is_success())
+                .unwrap_or(false);
+            backend.healthy.store(healthy, Ordering::SeqCst);
+
+            if let Ok(queue_depth) = backend.comfy.queue_depth().await {
+                backend.queue_depth.store(queue_depth, Ordering::SeqCst);
+            }
+        }
+    }
+}
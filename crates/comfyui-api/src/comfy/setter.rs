@@ -475,3 +475,224 @@ where
         }
     }
 }
+
+/// Inserts a node into a `Prompt` graph under a freshly allocated id, used by setters
+/// that restructure the graph rather than set a single field (e.g. `LoraSetter`).
+/// Existing `NodeConnection`s are not changed; rewiring them is left to the caller.
+pub trait InsertExt {
+    /// Allocates a fresh node id and inserts `node` under it.
+    ///
+    /// # Returns
+    ///
+    /// The id the node was inserted under.
+    fn insert_node<N: Node + 'static>(&mut self, node: N) -> String;
+}
+
+impl InsertExt for Prompt {
+    fn insert_node<N: Node + 'static>(&mut self, node: N) -> String {
+        let node_id = self.next_node_id();
+        self.set_node_by_id(&node_id, node);
+        node_id
+    }
+}
+
+/// A `Setter` for stacking LoRAs, given as `(name, model_strength, clip_strength)` tuples.
+///
+/// Unlike the field setters above, a LoRA is applied by inserting a `LoraLoader` node per
+/// requested LoRA between the `CheckpointLoaderSimple` and everything downstream of it,
+/// chaining each `LoraLoader`'s `model`/`clip` inputs off the previous stage and repointing
+/// the downstream `KSampler`/`SamplerCustom`/`CLIPTextEncode` connections to the last one.
+/// Because this mutates graph topology rather than a single field, `set_value` is not
+/// supported; apply a `LoraSetter` with `set`, `set_from` or `set_node`.
+///
+/// Only direct `KSampler`/`SamplerCustom`/`CLIPTextEncode` consumers of the checkpoint are
+/// rewired: any other node reading from the checkpoint (e.g. a `CLIPSetLastLayer` sitting
+/// between the checkpoint and the text encoders) is left pointing at the original
+/// checkpoint, so its CLIP output bypasses the LoRA stack entirely. Workflows that use one
+/// should apply their LoRAs upstream of it, not through this setter.
+pub struct LoraSetter {
+    /// The LoRAs to stack, in application order, as `(name, model_strength, clip_strength)`.
+    pub loras: Vec<(String, f32, f32)>,
+}
+
+impl From<Vec<(String, f32, f32)>> for LoraSetter {
+    fn from(loras: Vec<(String, f32, f32)>) -> Self {
+        Self { loras }
+    }
+}
+
+impl Setter<Vec<(String, f32, f32)>, LoraLoader> for LoraSetter {
+    fn set(&self, prompt: &mut Prompt) -> anyhow::Result<()> {
+        let checkpoint = find_node::<CheckpointLoaderSimple>(prompt, None)
+            .context("Failed to find node")?;
+        self.stack(prompt, &checkpoint)
+    }
+
+    fn set_from(&self, prompt: &mut Prompt, output_node: &str) -> anyhow::Result<()> {
+        let checkpoint = find_node::<CheckpointLoaderSimple>(prompt, Some(output_node))
+            .context("Failed to find node")?;
+        self.stack(prompt, &checkpoint)
+    }
+
+    fn set_node(&self, prompt: &mut Prompt, node: &str) -> anyhow::Result<()> {
+        self.stack(prompt, node)
+    }
+
+    fn set_value(&self, _node: &mut dyn Node) -> anyhow::Result<()> {
+        Err(anyhow!(
+            "LoraSetter mutates graph topology; use set/set_from/set_node instead of set_value"
+        ))
+    }
+}
+
+impl LoraSetter {
+    /// The `MODEL` output of a `CheckpointLoaderSimple`/`LoraLoader`.
+    const MODEL_OUTPUT: u32 = 0;
+    /// The `CLIP` output of a `CheckpointLoaderSimple`/`LoraLoader`.
+    const CLIP_OUTPUT: u32 = 1;
+
+    /// Inserts the requested LoRAs between `checkpoint` and everything downstream of it.
+    fn stack(&self, prompt: &mut Prompt, checkpoint: &str) -> anyhow::Result<()> {
+        let mut model_source = NodeConnection {
+            node_id: checkpoint.to_string(),
+            output_index: Self::MODEL_OUTPUT,
+        };
+        let mut clip_source = NodeConnection {
+            node_id: checkpoint.to_string(),
+            output_index: Self::CLIP_OUTPUT,
+        };
+
+        for (name, model_strength, clip_strength) in &self.loras {
+            let node_id = prompt.insert_node(LoraLoader {
+                lora_name: name.clone().into(),
+                strength_model: (*model_strength).into(),
+                strength_clip: (*clip_strength).into(),
+                model: model_source.clone(),
+                clip: clip_source.clone(),
+            });
+            model_source = NodeConnection {
+                node_id: node_id.clone(),
+                output_index: Self::MODEL_OUTPUT,
+            };
+            clip_source = NodeConnection {
+                node_id,
+                output_index: Self::CLIP_OUTPUT,
+            };
+        }
+
+        rewire_checkpoint_consumers(prompt, checkpoint, &model_source, &clip_source)
+    }
+}
+
+/// Repoints every `KSampler`/`SamplerCustom`'s `model` input and every `CLIPTextEncode`'s
+/// `clip` input that still points at `checkpoint` to `model_source`/`clip_source`
+/// respectively (the last node in a freshly inserted LoRA chain).
+///
+/// No other node type is considered: any other node reading from `checkpoint` (e.g. a
+/// `CLIPSetLastLayer`) keeps pointing at it, so whatever reads from that node bypasses the
+/// LoRA chain.
+fn rewire_checkpoint_consumers(
+    prompt: &mut Prompt,
+    checkpoint: &str,
+    model_source: &NodeConnection,
+    clip_source: &NodeConnection,
+) -> anyhow::Result<()> {
+    for node_id in prompt.node_ids() {
+        let Ok(node) = prompt.get_node_by_id_mut(&node_id) else {
+            continue;
+        };
+        if let Ok(ksampler) = as_node_mut::<KSampler>(node) {
+            if ksampler.model.node_id == checkpoint {
+                ksampler.model = model_source.clone();
+            }
+            continue;
+        }
+        if let Ok(sampler) = as_node_mut::<SamplerCustom>(node) {
+            if sampler.model.node_id == checkpoint {
+                sampler.model = model_source.clone();
+            }
+            continue;
+        }
+        if let Ok(clip_text_encode) = as_node_mut::<CLIPTextEncode>(node) {
+            if clip_text_encode.clip.node_id == checkpoint {
+                clip_text_encode.clip = clip_source.clone();
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stacks two LoRAs onto a minimal checkpoint -> CLIPTextEncode -> KSampler graph and
+    /// checks that both the sampler's `model` and the text encoder's `clip` end up pointing
+    /// at the last inserted `LoraLoader`, and that loader's own `model`/`clip` chain back,
+    /// through the first loader, to the original checkpoint.
+    #[test]
+    fn stacking_loras_rewires_model_and_clip_to_the_last_loader() {
+        let mut prompt = Prompt::default();
+
+        let checkpoint_id = prompt.insert_node(CheckpointLoaderSimple {
+            ckpt_name: "model.safetensors".to_string().into(),
+            ..Default::default()
+        });
+        let checkpoint_model = NodeConnection {
+            node_id: checkpoint_id.clone(),
+            output_index: LoraSetter::MODEL_OUTPUT,
+        };
+        let checkpoint_clip = NodeConnection {
+            node_id: checkpoint_id.clone(),
+            output_index: LoraSetter::CLIP_OUTPUT,
+        };
+
+        let clip_text_encode_id = prompt.insert_node(CLIPTextEncode {
+            text: "a cat".to_string().into(),
+            clip: checkpoint_clip,
+            ..Default::default()
+        });
+        let ksampler_id = prompt.insert_node(KSampler {
+            model: checkpoint_model,
+            positive: NodeConnection { node_id: clip_text_encode_id.clone(), output_index: 0 },
+            negative: NodeConnection { node_id: clip_text_encode_id.clone(), output_index: 0 },
+            ..Default::default()
+        });
+
+        let loras = vec![
+            ("style.safetensors".to_string(), 0.8, 0.8),
+            ("detail.safetensors".to_string(), 0.5, 0.5),
+        ];
+        LoraSetter::from(loras.clone())
+            .set_node(&mut prompt, &checkpoint_id)
+            .expect("failed to stack loras");
+
+        let ksampler = prompt
+            .get_node::<KSampler>(&ksampler_id)
+            .expect("KSampler missing");
+        let clip_text_encode = prompt
+            .get_node::<CLIPTextEncode>(&clip_text_encode_id)
+            .expect("CLIPTextEncode missing");
+
+        assert_ne!(ksampler.model.node_id, checkpoint_id);
+        assert_eq!(
+            ksampler.model.node_id, clip_text_encode.clip.node_id,
+            "model and clip chains should both end at the same last LoraLoader"
+        );
+
+        let last_lora_id = ksampler.model.node_id.clone();
+        let last_lora = prompt
+            .get_node::<LoraLoader>(&last_lora_id)
+            .expect("last LoraLoader missing");
+        assert_eq!(last_lora.lora_name, loras[1].0.clone().into());
+
+        let first_lora_id = last_lora.model.node_id.clone();
+        let first_lora = prompt
+            .get_node::<LoraLoader>(&first_lora_id)
+            .expect("first LoraLoader missing");
+        assert_eq!(first_lora.model.node_id, checkpoint_id);
+        assert_eq!(first_lora.lora_name, loras[0].0.clone().into());
+        assert_eq!(last_lora.clip.node_id, first_lora_id);
+        assert_eq!(first_lora.clip.node_id, checkpoint_id);
+    }
+}
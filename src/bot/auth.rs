@@ -0,0 +1,109 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// A user's authorization level.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// May generate images and manage other users.
+    Admin,
+    /// May generate images.
+    User,
+    /// May not use the bot at all.
+    Banned,
+}
+
+/// Persistent, role-based user store backed by a `sled` database at the bot's `db_path`.
+///
+/// Replaces the old `allowed_users: Vec<u64>` static list loaded once from `config.toml`:
+/// users and their roles can now be managed at runtime via the `/adduser`, `/removeuser`,
+/// `/setrole` and `/listusers` bot commands, without editing the config or restarting.
+#[derive(Clone)]
+pub struct AuthStore {
+    db: sled::Db,
+}
+
+impl AuthStore {
+    /// Opens (creating if necessary) an `AuthStore` at `db_path`, seeding `seed_admins` as
+    /// `Role::Admin` the first time the database is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `db_path` - Path to the `sled` database directory.
+    /// * `seed_admins` - User ids granted `Role::Admin` on first run, taken from the
+    ///   previous `allowed_users` config.
+    pub fn open(db_path: &str, seed_admins: &[u64]) -> anyhow::Result<Self> {
+        let db = sled::open(db_path).context("failed to open auth database")?;
+        Self::from_db(db, seed_admins)
+    }
+
+    /// Opens an in-memory `AuthStore`, seeding `seed_admins` as `Role::Admin`.
+    ///
+    /// Used when no `db_path` is configured; roles set at runtime are lost on restart.
+    pub fn open_in_memory(seed_admins: &[u64]) -> anyhow::Result<Self> {
+        let db = sled::Config::new()
+            .temporary(true)
+            .open()
+            .context("failed to open in-memory auth database")?;
+        Self::from_db(db, seed_admins)
+    }
+
+    fn from_db(db: sled::Db, seed_admins: &[u64]) -> anyhow::Result<Self> {
+        let store = Self { db };
+        if store.db.is_empty() {
+            for &id in seed_admins {
+                store.set_role(id, Role::Admin)?;
+            }
+        }
+        Ok(store)
+    }
+
+    /// Looks up the role of `user_id`, defaulting to `Role::Banned` for unknown users so
+    /// access must be explicitly granted.
+    pub fn role(&self, user_id: u64) -> anyhow::Result<Role> {
+        match self
+            .db
+            .get(user_id.to_be_bytes())
+            .context("failed to read user role")?
+        {
+            Some(bytes) => serde_json::from_slice(&bytes).context("failed to parse stored role"),
+            None => Ok(Role::Banned),
+        }
+    }
+
+    /// Sets the role of `user_id`, adding them to the store if they are not already in it.
+    pub fn set_role(&self, user_id: u64, role: Role) -> anyhow::Result<()> {
+        let bytes = serde_json::to_vec(&role).context("failed to serialize role")?;
+        self.db
+            .insert(user_id.to_be_bytes(), bytes)
+            .context("failed to persist role")?;
+        self.db.flush().context("failed to flush auth database")?;
+        Ok(())
+    }
+
+    /// Removes `user_id` from the store entirely.
+    pub fn remove_user(&self, user_id: u64) -> anyhow::Result<()> {
+        self.db
+            .remove(user_id.to_be_bytes())
+            .context("failed to remove user")?;
+        self.db.flush().context("failed to flush auth database")?;
+        Ok(())
+    }
+
+    /// Lists every known user and their role.
+    pub fn list_users(&self) -> anyhow::Result<Vec<(u64, Role)>> {
+        self.db
+            .iter()
+            .map(|entry| {
+                let (key, value) = entry.context("failed to read user entry")?;
+                let id = u64::from_be_bytes(
+                    key.as_ref()
+                        .try_into()
+                        .map_err(|_| anyhow::anyhow!("corrupt user id in auth database"))?,
+                );
+                let role =
+                    serde_json::from_slice(&value).context("failed to parse stored role")?;
+                Ok((id, role))
+            })
+            .collect()
+    }
+}
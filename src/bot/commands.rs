@@ -0,0 +1,188 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use teloxide::{prelude::*, types::InputFile, utils::command::BotCommands};
+
+use comfyui_api::{
+    comfy::{ComfyPool, PromptSetter, SeedSetter, SetterExt},
+    models::Prompt,
+};
+
+use crate::bot::auth::{AuthStore, Role};
+
+/// Message shown to a chat whose user is not authorized to use the bot.
+const DENIED_MESSAGE: &str = "You are not authorized to use this bot. Ask an admin to run /adduser for your user id.";
+
+/// Commands available to `Role::Admin` users for managing access at runtime.
+#[derive(BotCommands, Clone, Debug)]
+#[command(rename_rule = "lowercase", description = "Admin-only user management:")]
+pub enum AdminCommand {
+    #[command(description = "grant a user id Role::User access")]
+    AddUser(u64),
+    #[command(description = "remove a user id from the store")]
+    RemoveUser(u64),
+    #[command(description = "set a user id's role: admin, user or banned")]
+    SetRole { user_id: u64, role: String },
+    #[command(description = "list every known user and their role")]
+    ListUsers,
+}
+
+/// Commands available to any authorized (`Role::Admin` or `Role::User`) user.
+#[derive(BotCommands, Clone, Debug)]
+#[command(rename_rule = "lowercase", description = "Image generation:")]
+pub enum UserCommand {
+    #[command(description = "generate an image from a text prompt")]
+    Generate(String),
+}
+
+/// Returns `Ok(())` if `user_id` holds `Role::Admin`, otherwise sends `DENIED_MESSAGE` to
+/// `chat` and returns `Err`.
+async fn require_admin(bot: &Bot, chat: ChatId, store: &AuthStore, user_id: u64) -> anyhow::Result<()> {
+    if store.role(user_id)? == Role::Admin {
+        return Ok(());
+    }
+    bot.send_message(chat, DENIED_MESSAGE).await?;
+    Err(anyhow::anyhow!("user {user_id} is not an admin"))
+}
+
+/// Gate for the image-generation handlers: returns `Ok(())` for `Role::Admin` and
+/// `Role::User`, or sends `DENIED_MESSAGE` and returns `Err` for `Role::Banned` and
+/// unknown users.
+pub async fn require_authorized(
+    bot: &Bot,
+    chat: ChatId,
+    store: &AuthStore,
+    user_id: u64,
+) -> anyhow::Result<()> {
+    if store.role(user_id)? != Role::Banned {
+        return Ok(());
+    }
+    bot.send_message(chat, DENIED_MESSAGE).await?;
+    Err(anyhow::anyhow!("user {user_id} is not authorized"))
+}
+
+/// Dispatches an `AdminCommand`, gating every variant on the sender holding `Role::Admin`.
+pub async fn handle_admin_command(
+    bot: Bot,
+    msg: Message,
+    command: AdminCommand,
+    store: AuthStore,
+) -> anyhow::Result<()> {
+    let Some(sender) = msg.from() else {
+        return Ok(());
+    };
+    let sender_id = sender.id.0;
+
+    if require_admin(&bot, msg.chat.id, &store, sender_id)
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let reply = match command {
+        AdminCommand::AddUser(user_id) => {
+            store.set_role(user_id, Role::User)?;
+            format!("Added user {user_id} with role User.")
+        }
+        AdminCommand::RemoveUser(user_id) => {
+            store.remove_user(user_id)?;
+            format!("Removed user {user_id}.")
+        }
+        AdminCommand::SetRole { user_id, role } => match parse_role(&role) {
+            Some(role) => {
+                store.set_role(user_id, role)?;
+                format!("Set user {user_id}'s role to {role:?}.")
+            }
+            None => "Unknown role, expected one of: admin, user, banned.".to_string(),
+        },
+        AdminCommand::ListUsers => {
+            let mut users = store.list_users()?;
+            users.sort_by_key(|(id, _)| *id);
+            if users.is_empty() {
+                "No users in the store yet.".to_string()
+            } else {
+                users
+                    .into_iter()
+                    .map(|(id, role)| format!("{id}: {role:?}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+    };
+
+    bot.send_message(msg.chat.id, reply).await?;
+    Ok(())
+}
+
+/// Dispatches a `UserCommand`, gating every variant on the sender not holding
+/// `Role::Banned`.
+pub async fn handle_user_command(
+    bot: Bot,
+    msg: Message,
+    command: UserCommand,
+    store: AuthStore,
+    pool: ComfyPool,
+    workflow: Prompt,
+) -> anyhow::Result<()> {
+    let Some(sender) = msg.from() else {
+        return Ok(());
+    };
+    let sender_id = sender.id.0;
+
+    if require_authorized(&bot, msg.chat.id, &store, sender_id)
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let UserCommand::Generate(text) = command;
+
+    let mut prompt = workflow.clone();
+    if let Err(err) = prompt.set::<PromptSetter>(text) {
+        bot.send_message(msg.chat.id, format!("Failed to set prompt text: {err}"))
+            .await?;
+        return Ok(());
+    }
+    if let Err(err) = prompt.set::<SeedSetter>(random_seed()) {
+        bot.send_message(msg.chat.id, format!("Failed to set seed: {err}"))
+            .await?;
+        return Ok(());
+    }
+
+    // Every /generate call sets a fresh seed, so the resulting Prompt is never identical
+    // to a previous one and the result cache (keyed on the whole Prompt) could never hit.
+    // Bypass it rather than let it grow on disk for writes that will never be read back.
+    match pool.execute_prompt_no_cache(&prompt).await {
+        Ok(images) => {
+            for image in images {
+                bot.send_photo(msg.chat.id, InputFile::memory(image.bytes))
+                    .await?;
+            }
+        }
+        Err(err) => {
+            bot.send_message(msg.chat.id, format!("Generation failed: {err}"))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// A seed that varies between calls, so repeated identical `/generate` text produces a
+/// new image each time.
+fn random_seed() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as i64)
+        .unwrap_or_default()
+}
+
+fn parse_role(role: &str) -> Option<Role> {
+    match role.to_ascii_lowercase().as_str() {
+        "admin" => Some(Role::Admin),
+        "user" => Some(Role::User),
+        "banned" => Some(Role::Banned),
+        _ => None,
+    }
+}
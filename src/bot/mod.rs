@@ -0,0 +1,88 @@
+use anyhow::Context;
+use teloxide::{dispatching::UpdateFilterExt, prelude::*};
+
+use comfyui_api::{comfy::ComfyPool, models::Prompt};
+
+pub mod auth;
+pub mod commands;
+
+use auth::AuthStore;
+use commands::{AdminCommand, UserCommand};
+
+/// Runs the Telegram bot until it is shut down.
+///
+/// # Arguments
+///
+/// * `api_key` - The Telegram bot token.
+/// * `allowed_users` - User ids seeded as `Role::Admin` the first time the auth database
+///   is created; kept for backwards compatibility with existing `config.toml` files.
+/// * `db_path` - Path to the auth database directory. Falls back to an in-memory store,
+///   re-seeded from `allowed_users` on every start, if unset. Also enables the ComfyUI
+///   result cache, stored under a `cache` subdirectory so it doesn't collide with the
+///   auth database's own files.
+/// * `sd_api_urls` - One or more ComfyUI backend base urls to load-balance across.
+/// * `workflow_path` - Path to an exported ComfyUI workflow JSON (API format), used as the
+///   base graph for `/generate`. Same format `crates/comfyui-api/examples/comfy.rs` reads
+///   from stdin.
+pub async fn run_bot(
+    api_key: String,
+    allowed_users: Vec<u64>,
+    db_path: Option<String>,
+    sd_api_urls: Vec<String>,
+    workflow_path: String,
+) -> anyhow::Result<()> {
+    let store = open_auth_store(db_path.as_deref(), &allowed_users)?;
+    let pool = open_comfy_pool(db_path.as_deref(), sd_api_urls)
+        .context("failed to construct ComfyUI backend pool")?;
+    let workflow = load_workflow(&workflow_path)
+        .with_context(|| format!("failed to load workflow from {workflow_path}"))?;
+
+    let bot = Bot::new(api_key);
+
+    let handler = dptree::entry()
+        .branch(
+            Update::filter_message()
+                .filter_command::<AdminCommand>()
+                .endpoint(commands::handle_admin_command),
+        )
+        .branch(
+            Update::filter_message()
+                .filter_command::<UserCommand>()
+                .endpoint(commands::handle_user_command),
+        );
+
+    Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![store, pool, workflow])
+        .enable_ctrlc_handler()
+        .build()
+        .dispatch()
+        .await;
+
+    Ok(())
+}
+
+/// Reads and deserializes the base workflow `Prompt` from `path`.
+fn load_workflow(path: &str) -> anyhow::Result<Prompt> {
+    let json = std::fs::read_to_string(path).context("failed to read workflow file")?;
+    serde_json::from_str(&json).context("failed to parse workflow json")
+}
+
+/// Opens the auth database at `db_path`, or an in-memory one re-seeded from
+/// `allowed_users` if no `db_path` is configured.
+fn open_auth_store(db_path: Option<&str>, allowed_users: &[u64]) -> anyhow::Result<AuthStore> {
+    match db_path {
+        Some(db_path) => AuthStore::open(db_path, allowed_users),
+        None => AuthStore::open_in_memory(allowed_users),
+    }
+}
+
+/// Constructs the backend pool, enabling the result cache under `db_path`'s `cache`
+/// subdirectory when `db_path` is configured.
+fn open_comfy_pool(db_path: Option<&str>, sd_api_urls: Vec<String>) -> anyhow::Result<ComfyPool> {
+    match db_path {
+        Some(db_path) => {
+            ComfyPool::new_with_cache(sd_api_urls, std::path::Path::new(db_path).join("cache"))
+        }
+        None => ComfyPool::new(sd_api_urls),
+    }
+}
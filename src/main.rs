@@ -10,12 +10,41 @@ use tracing_subscriber::{EnvFilter, FmtSubscriber};
 pub mod api;
 pub mod bot;
 
+/// One or more ComfyUI backend base urls. A single backend keeps existing configs
+/// working; multiple backends are load-balanced across by a `ComfyPool`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum SdApiUrl {
+    Single(String),
+    Pool(Vec<String>),
+}
+
+impl Default for SdApiUrl {
+    fn default() -> Self {
+        Self::Single(String::new())
+    }
+}
+
+impl SdApiUrl {
+    /// Normalizes into the list of backend urls to load-balance across.
+    fn into_urls(self) -> Vec<String> {
+        match self {
+            Self::Single(url) => vec![url],
+            Self::Pool(urls) => urls,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 struct Config {
     api_key: String,
     allowed_users: Vec<u64>,
     db_path: Option<String>,
-    sd_api_url: String,
+    sd_api_url: SdApiUrl,
+    /// Path to an exported ComfyUI workflow JSON (API format), used as the base graph for
+    /// the `/generate` command. See `crates/comfyui-api/examples/comfy.rs` for the same
+    /// format read from stdin.
+    workflow_path: String,
 }
 
 #[tokio::main]
@@ -47,7 +76,8 @@ async fn main() -> anyhow::Result<()> {
         config.api_key,
         config.allowed_users,
         config.db_path,
-        config.sd_api_url,
+        config.sd_api_url.into_urls(),
+        config.workflow_path,
     )
     .await?;
 